@@ -0,0 +1,47 @@
+use inkwell::debug_info::{
+    DICompileUnit, DWARFEmissionKind, DWARFSourceLanguage, DebugInfoBuilder,
+};
+use inkwell::module::Module;
+
+pub struct DebugInfo<'ctx> {
+    builder: DebugInfoBuilder<'ctx>,
+    compile_unit: DICompileUnit<'ctx>,
+}
+
+impl<'ctx> DebugInfo<'ctx> {
+    pub fn new(llvm_mod: &Module<'ctx>, file_name: &str, directory: &str) -> Self {
+        let (builder, compile_unit) = llvm_mod.create_debug_info_builder(
+            true,
+            DWARFSourceLanguage::C,
+            file_name,
+            directory,
+            "midlevel-ir",
+            false,
+            "",
+            0,
+            "",
+            DWARFEmissionKind::Full,
+            0,
+            false,
+            false,
+            "",
+            "",
+        );
+        DebugInfo {
+            builder,
+            compile_unit,
+        }
+    }
+
+    pub fn builder(&self) -> &DebugInfoBuilder<'ctx> {
+        &self.builder
+    }
+
+    pub fn compile_unit(&self) -> &DICompileUnit<'ctx> {
+        &self.compile_unit
+    }
+
+    pub fn finalize(&self) {
+        self.builder.finalize();
+    }
+}
@@ -0,0 +1,8 @@
+use crate::function::BlockId;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CfgError {
+    MissingTerminator(BlockId),
+    MisplacedTerminator(BlockId),
+    UnknownBranchTarget { from: BlockId, target: BlockId },
+}
@@ -1,14 +1,15 @@
 use std::ops::Range;
 
-use crate::{instruction::{InstId, Instruction}, types::MIRType};
+use crate::{instruction::{InstId, Instruction}, span::Span, types::MIRType};
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct BlockId(pub usize);
 
 #[derive(Debug)]
 pub struct Block<'ctx> {
     name: &'ctx str,
     range: Range<InstId>,
+    successors: Vec<BlockId>,
 }
 
 impl<'ctx> Block<'ctx> {
@@ -16,6 +17,7 @@ impl<'ctx> Block<'ctx> {
         Block {
             name,
             range: start..start,
+            successors: Vec::new(),
         }
     }
 
@@ -33,6 +35,14 @@ impl<'ctx> Block<'ctx> {
         self.range.clone()
     }
 
+    pub fn add_successor(&mut self, succ: BlockId) {
+        self.successors.push(succ);
+    }
+
+    pub fn get_successors(&self) -> &[BlockId] {
+        &self.successors
+    }
+
     pub fn get_instructions<'f>(&self, func: &'f Function<'ctx>) -> &'f [Instruction] {
         let start = self.range.start.0;
         let end = self.range.end.0;
@@ -47,17 +57,21 @@ pub struct FuncId(pub usize);
 pub struct Function<'ctx> {
     name: &'ctx str,
     ret_type: MIRType,
+    params: Vec<MIRType>,
     instructions: Vec<Instruction>,
+    spans: Vec<Option<Span<'ctx>>>,
     blocks: Vec<Block<'ctx>>,
     inst_id: usize,
 }
 
 impl<'ctx> Function<'ctx> {
-    pub fn new(name: &'ctx str, ret_type: MIRType) -> Self {
+    pub fn new(name: &'ctx str, ret_type: MIRType, params: Vec<MIRType>) -> Self {
         Function {
             name,
             ret_type,
+            params,
             instructions: Vec::new(),
+            spans: Vec::new(),
             blocks: Vec::new(),
             inst_id: 0,
         }
@@ -66,10 +80,25 @@ impl<'ctx> Function<'ctx> {
     pub fn add_instruction(&mut self, instruction: Instruction) -> InstId {
         let inst_id = InstId(self.inst_id);
         self.instructions.push(instruction);
+        self.spans.push(None);
         self.inst_id += 1;
         inst_id
     }
 
+    pub fn add_instruction_with_span(
+        &mut self,
+        instruction: Instruction,
+        span: Span<'ctx>,
+    ) -> InstId {
+        let inst_id = self.add_instruction(instruction);
+        self.spans[inst_id.0] = Some(span);
+        inst_id
+    }
+
+    pub fn get_span(&self, inst_id: InstId) -> Option<Span<'ctx>> {
+        self.spans.get(inst_id.0).copied().flatten()
+    }
+
     pub fn add_block(&mut self, block: Block<'ctx>) -> BlockId {
         let block_id = BlockId(self.blocks.len());
         self.blocks.push(block);
@@ -105,6 +134,10 @@ impl<'ctx> Function<'ctx> {
     }
 
     pub fn get_ret_type(&self) -> MIRType {
-        self.ret_type
+        self.ret_type.clone()
+    }
+
+    pub fn get_params(&self) -> &[MIRType] {
+        &self.params
     }
 }
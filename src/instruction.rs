@@ -1,4 +1,8 @@
-use crate::{types::MIRType, value::Value};
+use crate::{
+    function::{BlockId, FuncId},
+    types::MIRType,
+    value::Value,
+};
 
 #[derive(Debug)]
 pub struct DefineInst {
@@ -12,7 +16,7 @@ impl DefineInst {
     }
 
     pub fn get_type(&self) -> MIRType {
-        self.type_
+        self.type_.clone()
     }
 
     pub fn get_value(&self) -> &Value {
@@ -40,24 +44,62 @@ impl AssignInst {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinOp {
+    Add,
+    Sub,
+    Mul,
+    SDiv,
+    UDiv,
+    SRem,
+    URem,
+    And,
+    Or,
+    Xor,
+    Shl,
+    LShr,
+    AShr,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl BinOp {
+    pub fn is_comparison(&self) -> bool {
+        matches!(
+            self,
+            BinOp::Eq | BinOp::Ne | BinOp::Lt | BinOp::Le | BinOp::Gt | BinOp::Ge
+        )
+    }
+}
+
 #[derive(Debug)]
-pub struct AddInst {
+pub struct BinaryOpInst {
     dest: Value,
+    op: BinOp,
     lhs: Value,
     rhs: Value,
     type_: MIRType,
 }
 
-impl AddInst {
-    pub fn new(dest: Value, lhs: Value, rhs: Value, type_: MIRType) -> Self {
-        AddInst {
+impl BinaryOpInst {
+    pub fn new(dest: Value, op: BinOp, lhs: Value, rhs: Value, type_: MIRType) -> Self {
+        BinaryOpInst {
             dest,
+            op,
             lhs,
             rhs,
             type_,
         }
     }
 
+    pub fn get_op(&self) -> BinOp {
+        self.op
+    }
+
     pub fn get_lhs(&self) -> &Value {
         &self.lhs
     }
@@ -67,7 +109,7 @@ impl AddInst {
     }
 
     pub fn get_type(&self) -> MIRType {
-        self.type_
+        self.type_.clone()
     }
 
     pub fn get_dest(&self) -> &Value {
@@ -90,13 +132,128 @@ impl RetInst {
     }
 }
 
+#[derive(Debug)]
+pub struct CallInst {
+    callee: FuncId,
+    args: Vec<Value>,
+    ret_ty: MIRType,
+}
+
+impl CallInst {
+    pub fn new(callee: FuncId, args: Vec<Value>, ret_ty: MIRType) -> Self {
+        CallInst {
+            callee,
+            args,
+            ret_ty,
+        }
+    }
+
+    pub fn get_callee(&self) -> FuncId {
+        self.callee
+    }
+
+    pub fn get_args(&self) -> &[Value] {
+        &self.args
+    }
+
+    pub fn get_ret_ty(&self) -> MIRType {
+        self.ret_ty.clone()
+    }
+}
+
+/// Computes the address of a field of a `Struct` or an element of an `Array`, given a pointer
+/// to the aggregate. `agg_ty` names the pointee so the lowering knows how to index it; `index`
+/// picks the struct field or array element.
+#[derive(Debug)]
+pub struct GepInst {
+    base: Value,
+    agg_ty: MIRType,
+    index: u64,
+}
+
+impl GepInst {
+    pub fn new(base: Value, agg_ty: MIRType, index: u64) -> Self {
+        GepInst {
+            base,
+            agg_ty,
+            index,
+        }
+    }
+
+    pub fn get_base(&self) -> &Value {
+        &self.base
+    }
+
+    pub fn get_agg_ty(&self) -> MIRType {
+        self.agg_ty.clone()
+    }
+
+    pub fn get_index(&self) -> u64 {
+        self.index
+    }
+}
+
+#[derive(Debug)]
+pub struct BrInst {
+    target: BlockId,
+}
+
+impl BrInst {
+    pub fn new(target: BlockId) -> Self {
+        BrInst { target }
+    }
+
+    pub fn get_target(&self) -> BlockId {
+        self.target
+    }
+}
+
+#[derive(Debug)]
+pub struct CondBrInst {
+    cond: Value,
+    then_bb: BlockId,
+    else_bb: BlockId,
+}
+
+impl CondBrInst {
+    pub fn new(cond: Value, then_bb: BlockId, else_bb: BlockId) -> Self {
+        CondBrInst {
+            cond,
+            then_bb,
+            else_bb,
+        }
+    }
+
+    pub fn get_cond(&self) -> &Value {
+        &self.cond
+    }
+
+    pub fn get_then_bb(&self) -> BlockId {
+        self.then_bb
+    }
+
+    pub fn get_else_bb(&self) -> BlockId {
+        self.else_bb
+    }
+}
+
 #[derive(Debug)]
 pub enum Instruction {
     Define(DefineInst),
     Assign(AssignInst),
-    Add(AddInst),
+    BinaryOp(BinaryOpInst),
+    Call(CallInst),
+    Gep(GepInst),
+    Br(BrInst),
+    CondBr(CondBrInst),
     Ret(RetInst),
 }
 
+impl Instruction {
+    pub fn is_terminator(&self) -> bool {
+        matches!(self, Instruction::Br(_) | Instruction::CondBr(_) | Instruction::Ret(_))
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct InstId(pub usize);
@@ -1,23 +1,33 @@
 use std::collections::HashMap;
 
-use function::{Block, Function};
+use debug::DebugInfo;
+use function::{Block, BlockId, Function};
 use inkwell::{
-    OptimizationLevel,
+    AddressSpace, FloatPredicate, IntPredicate, OptimizationLevel,
+    attributes::{Attribute, AttributeLoc},
+    basic_block::BasicBlock,
     builder::Builder,
     context::Context,
-    passes::{PassBuilderOptions, PassManager},
+    debug_info::{AsDIScope, DIFlags, DIFlagsConstants, DISubprogram},
+    execution_engine::JitFunction,
+    passes::PassBuilderOptions,
     targets::{CodeModel, InitializationConfig, RelocMode, Target, TargetMachine},
-    types::{BasicType, BasicTypeEnum},
-    values::{BasicValue, BasicValueEnum},
+    types::{AnyType, BasicMetadataTypeEnum, BasicType, BasicTypeEnum},
+    values::{BasicMetadataValueEnum, BasicValue, BasicValueEnum},
 };
-use instruction::{AddInst, DefineInst, InstId, Instruction, RetInst};
+use instruction::{BinOp, BinaryOpInst, DefineInst, InstId, Instruction, RetInst};
 use module::Module;
 use types::MIRType;
 use value::Value;
 
+use crate::error::CfgError;
+
+pub mod debug;
+pub mod error;
 pub mod function;
 pub mod instruction;
 pub mod module;
+pub mod span;
 pub mod types;
 pub mod value;
 
@@ -26,11 +36,79 @@ struct Codegen<'ctx> {
     llvm_mod: inkwell::module::Module<'ctx>,
     llvm_builder: Builder<'ctx>,
     namend: HashMap<InstId, BasicValueEnum<'ctx>>,
+    param_values: HashMap<usize, BasicValueEnum<'ctx>>,
+    debug_info: Option<DebugInfo<'ctx>>,
+    opt_level: OptimizationLevel,
 }
 
-fn to_llvm_type<'ctx>(ty: MIRType, codegen: &Codegen<'ctx>) -> BasicTypeEnum<'ctx> {
-    match ty {
-        MIRType::Int32 => codegen.llvm_ctx.i32_type().into(),
+impl<'ctx> Codegen<'ctx> {
+    /// Creates a codegen context for `module_name`, optionally wiring up a DWARF `DebugInfo`
+    /// builder in the same step. There is no separate "enable debug info" flag to fall out of
+    /// sync with it: `debug_info` is `Some` iff `debug` was `Some`.
+    fn new(llvm_ctx: &'ctx Context, module_name: &str, debug: Option<(&str, &str)>) -> Self {
+        let llvm_mod = llvm_ctx.create_module(module_name);
+        let debug_info = debug
+            .map(|(file_name, directory)| DebugInfo::new(&llvm_mod, file_name, directory));
+
+        Codegen {
+            llvm_ctx,
+            llvm_mod,
+            llvm_builder: llvm_ctx.create_builder(),
+            namend: HashMap::new(),
+            param_values: HashMap::new(),
+            debug_info,
+            opt_level: OptimizationLevel::Default,
+        }
+    }
+
+    fn debug_enabled(&self) -> bool {
+        self.debug_info.is_some()
+    }
+
+    /// JIT-compiles the module and invokes `entry`, widening its result to `i64`.
+    ///
+    /// `entry` must name a function taking no arguments and returning `i32` — the same shape
+    /// the sample `main` produces.
+    fn jit_run(&self, entry: &str) -> Result<i64, String> {
+        let execution_engine = self
+            .llvm_mod
+            .create_jit_execution_engine(OptimizationLevel::None)
+            .map_err(|err| err.to_string())?;
+
+        let main_fn: JitFunction<unsafe extern "C" fn() -> i32> =
+            unsafe { execution_engine.get_function(entry) }.map_err(|err| err.to_string())?;
+
+        Ok(unsafe { main_fn.call() } as i64)
+    }
+
+    /// Runs the new-pass-manager pipeline over the whole module at `self.opt_level`.
+    ///
+    /// Returns straight away for `OptimizationLevel::None`; otherwise builds the `TargetMachine`
+    /// from the host triple first so the passes it runs are target-aware.
+    fn optimize(&self) -> Result<(), String> {
+        let passes = match self.opt_level {
+            OptimizationLevel::None => return Ok(()),
+            OptimizationLevel::Less => "default<O1>",
+            OptimizationLevel::Default => "default<O2>",
+            OptimizationLevel::Aggressive => "default<O3>",
+        };
+
+        let triple = TargetMachine::get_default_triple();
+        let target = Target::from_triple(&triple).map_err(|err| err.to_string())?;
+        let target_machine = target
+            .create_target_machine(
+                &triple,
+                &TargetMachine::get_host_cpu_name().to_string(),
+                &TargetMachine::get_host_cpu_features().to_string(),
+                self.opt_level,
+                RelocMode::Default,
+                CodeModel::Default,
+            )
+            .ok_or_else(|| "failed to create target machine for host triple".to_string())?;
+
+        self.llvm_mod
+            .run_passes(passes, &target_machine, PassBuilderOptions::create())
+            .map_err(|err| err.to_string())
     }
 }
 
@@ -43,23 +121,239 @@ fn to_llvm_value<'ctx>(value: Value, codegen: &Codegen<'ctx>) -> BasicValueEnum<
                 .expect("Instruction not found in namend map");
             return llvm_value.clone();
         }
-        Value::ConstantInt(literal) => codegen
-            .llvm_ctx
-            .i64_type()
-            .const_int(literal as u64, false)
-            .into(),
-        Value::ConstantFloat(literal) => codegen.llvm_ctx.f64_type().const_float(literal).into(),
+        Value::Param(index) => {
+            let llvm_value = codegen
+                .param_values
+                .get(&index)
+                .expect("Param not found in param_values map");
+            return llvm_value.clone();
+        }
+        Value::ConstantInt(literal, ty) => {
+            let int_type = match ty.llvm_type(codegen.llvm_ctx) {
+                BasicTypeEnum::IntType(int_type) => int_type,
+                _ => panic!("ConstantInt must carry an integer MIRType"),
+            };
+            int_type.const_int(literal as u64, ty.is_signed()).into()
+        }
+        Value::ConstantFloat(literal, ty) => {
+            let float_type = match ty.llvm_type(codegen.llvm_ctx) {
+                BasicTypeEnum::FloatType(float_type) => float_type,
+                _ => panic!("ConstantFloat must carry a float MIRType"),
+            };
+            float_type.const_float(literal).into()
+        }
     }
 }
 
-fn compile_block<'f, 'ctx>(block: &'f Block, func: &'f Function, codegen: &'f mut Codegen<'ctx>) {
+/// Resolves an operand to its runtime value, loading through a `Define` slot's alloca pointer
+/// when needed and passing already-materialized values (params, constants, prior binary-op
+/// results) through as-is. Every binary op reuses this instead of copy-pasting the match.
+fn operand_as_value<'ctx>(
+    value: Value,
+    ty: &MIRType,
+    codegen: &mut Codegen<'ctx>,
+) -> BasicValueEnum<'ctx> {
+    if let Value::Instruction(inst_id) = value {
+        let slot = *codegen
+            .namend
+            .get(&inst_id)
+            .expect("Instruction not found in namend map");
+        if slot.is_pointer_value() {
+            let llvm_ty = ty.llvm_type(codegen.llvm_ctx);
+            return codegen
+                .llvm_builder
+                .build_load(llvm_ty, slot.into_pointer_value(), "operand_load")
+                .expect("Failed to load operand");
+        }
+        return slot;
+    }
+    to_llvm_value(value, codegen)
+}
+
+/// Dispatches a `BinOp` on the operand `MIRType`, emitting the integer, float or comparison
+/// builder call it needs.
+fn build_binary_op<'ctx>(
+    op: BinOp,
+    ty: &MIRType,
+    lhs: BasicValueEnum<'ctx>,
+    rhs: BasicValueEnum<'ctx>,
+    codegen: &Codegen<'ctx>,
+) -> BasicValueEnum<'ctx> {
+    if let MIRType::Float(_) = ty {
+        let lhs = lhs.into_float_value();
+        let rhs = rhs.into_float_value();
+        let builder = &codegen.llvm_builder;
+        return match op {
+            BinOp::Add => builder.build_float_add(lhs, rhs, "fadd").unwrap().as_basic_value_enum(),
+            BinOp::Sub => builder.build_float_sub(lhs, rhs, "fsub").unwrap().as_basic_value_enum(),
+            BinOp::Mul => builder.build_float_mul(lhs, rhs, "fmul").unwrap().as_basic_value_enum(),
+            BinOp::SDiv | BinOp::UDiv => {
+                builder.build_float_div(lhs, rhs, "fdiv").unwrap().as_basic_value_enum()
+            }
+            BinOp::SRem | BinOp::URem => {
+                builder.build_float_rem(lhs, rhs, "frem").unwrap().as_basic_value_enum()
+            }
+            BinOp::Eq => builder
+                .build_float_compare(FloatPredicate::OEQ, lhs, rhs, "fcmp")
+                .unwrap()
+                .as_basic_value_enum(),
+            BinOp::Ne => builder
+                .build_float_compare(FloatPredicate::ONE, lhs, rhs, "fcmp")
+                .unwrap()
+                .as_basic_value_enum(),
+            BinOp::Lt => builder
+                .build_float_compare(FloatPredicate::OLT, lhs, rhs, "fcmp")
+                .unwrap()
+                .as_basic_value_enum(),
+            BinOp::Le => builder
+                .build_float_compare(FloatPredicate::OLE, lhs, rhs, "fcmp")
+                .unwrap()
+                .as_basic_value_enum(),
+            BinOp::Gt => builder
+                .build_float_compare(FloatPredicate::OGT, lhs, rhs, "fcmp")
+                .unwrap()
+                .as_basic_value_enum(),
+            BinOp::Ge => builder
+                .build_float_compare(FloatPredicate::OGE, lhs, rhs, "fcmp")
+                .unwrap()
+                .as_basic_value_enum(),
+            BinOp::And | BinOp::Or | BinOp::Xor | BinOp::Shl | BinOp::LShr | BinOp::AShr => {
+                panic!("bitwise op {op:?} requires an integer MIRType")
+            }
+        };
+    }
+
+    let lhs = lhs.into_int_value();
+    let rhs = rhs.into_int_value();
+    let signed = ty.is_signed();
+    let builder = &codegen.llvm_builder;
+    let int_predicate = match op {
+        BinOp::Eq => Some(IntPredicate::EQ),
+        BinOp::Ne => Some(IntPredicate::NE),
+        BinOp::Lt => Some(if signed { IntPredicate::SLT } else { IntPredicate::ULT }),
+        BinOp::Le => Some(if signed { IntPredicate::SLE } else { IntPredicate::ULE }),
+        BinOp::Gt => Some(if signed { IntPredicate::SGT } else { IntPredicate::UGT }),
+        BinOp::Ge => Some(if signed { IntPredicate::SGE } else { IntPredicate::UGE }),
+        _ => None,
+    };
+    if let Some(predicate) = int_predicate {
+        return builder
+            .build_int_compare(predicate, lhs, rhs, "icmp")
+            .unwrap()
+            .as_basic_value_enum();
+    }
+
+    match op {
+        BinOp::Add => builder.build_int_add(lhs, rhs, "add").unwrap().as_basic_value_enum(),
+        BinOp::Sub => builder.build_int_sub(lhs, rhs, "sub").unwrap().as_basic_value_enum(),
+        BinOp::Mul => builder.build_int_mul(lhs, rhs, "mul").unwrap().as_basic_value_enum(),
+        BinOp::SDiv => builder
+            .build_int_signed_div(lhs, rhs, "sdiv")
+            .unwrap()
+            .as_basic_value_enum(),
+        BinOp::UDiv => builder
+            .build_int_unsigned_div(lhs, rhs, "udiv")
+            .unwrap()
+            .as_basic_value_enum(),
+        BinOp::SRem => builder
+            .build_int_signed_rem(lhs, rhs, "srem")
+            .unwrap()
+            .as_basic_value_enum(),
+        BinOp::URem => builder
+            .build_int_unsigned_rem(lhs, rhs, "urem")
+            .unwrap()
+            .as_basic_value_enum(),
+        BinOp::And => builder.build_and(lhs, rhs, "and").unwrap().as_basic_value_enum(),
+        BinOp::Or => builder.build_or(lhs, rhs, "or").unwrap().as_basic_value_enum(),
+        BinOp::Xor => builder.build_xor(lhs, rhs, "xor").unwrap().as_basic_value_enum(),
+        BinOp::Shl => builder
+            .build_left_shift(lhs, rhs, "shl")
+            .unwrap()
+            .as_basic_value_enum(),
+        BinOp::LShr => builder
+            .build_right_shift(lhs, rhs, false, "lshr")
+            .unwrap()
+            .as_basic_value_enum(),
+        BinOp::AShr => builder
+            .build_right_shift(lhs, rhs, true, "ashr")
+            .unwrap()
+            .as_basic_value_enum(),
+        BinOp::Eq | BinOp::Ne | BinOp::Lt | BinOp::Le | BinOp::Gt | BinOp::Ge => unreachable!(),
+    }
+}
+
+/// Verifies every block ends in exactly one terminator, then records its branch targets as
+/// `successors` so later passes can walk the CFG without re-matching the terminator instruction.
+fn verify_cfg(function: &mut Function) -> Result<(), CfgError> {
+    let block_count = function.get_blocks().len();
+    for i in 0..block_count {
+        let id = BlockId(i);
+        let block = function.get_block(id).unwrap();
+        let instructions = block.get_instructions(function);
+        let terminator_pos = instructions.iter().position(|inst| inst.is_terminator());
+        match terminator_pos {
+            None => return Err(CfgError::MissingTerminator(id)),
+            Some(pos) if pos != instructions.len() - 1 => {
+                return Err(CfgError::MisplacedTerminator(id));
+            }
+            _ => {}
+        }
+
+        let targets: Vec<BlockId> = match instructions.last() {
+            Some(Instruction::Br(br)) => vec![br.get_target()],
+            Some(Instruction::CondBr(cond_br)) => {
+                vec![cond_br.get_then_bb(), cond_br.get_else_bb()]
+            }
+            _ => Vec::new(),
+        };
+
+        let block = function.get_block_mut(id).unwrap();
+        for target in targets {
+            block.add_successor(target);
+        }
+
+        for &target in function.get_block(id).unwrap().get_successors() {
+            if target.0 >= block_count {
+                return Err(CfgError::UnknownBranchTarget { from: id, target });
+            }
+        }
+    }
+    Ok(())
+}
+
+fn compile_block<'f, 'ctx>(
+    block: &'f Block,
+    func: &'f Function,
+    module: &'f Module,
+    codegen: &'f mut Codegen<'ctx>,
+    blocks: &HashMap<BlockId, BasicBlock<'ctx>>,
+    subprogram: Option<DISubprogram<'ctx>>,
+) {
     let instructions = block.get_instructions(func);
     let range = block.get_range();
     for (i, inst) in instructions.iter().enumerate() {
         let inst_id = InstId(range.start.0 + i);
+
+        if let Some(subprogram) = subprogram {
+            if let Some(span) = func.get_span(inst_id) {
+                let debug_info = codegen
+                    .debug_info
+                    .as_ref()
+                    .expect("debug info builder not initialized");
+                let location = debug_info.builder().create_debug_location(
+                    codegen.llvm_ctx,
+                    span.line,
+                    span.col,
+                    subprogram.as_debug_info_scope(),
+                    None,
+                );
+                codegen.llvm_builder.set_current_debug_location(location);
+            }
+        }
+
         match inst {
             Instruction::Define(define_inst) => {
-                let llvm_type = to_llvm_type(define_inst.get_type(), codegen);
+                let llvm_type = define_inst.get_type().llvm_type(codegen.llvm_ctx);
                 let llvm_value = codegen
                     .llvm_builder
                     .build_alloca(llvm_type, "temp")
@@ -82,62 +376,121 @@ fn compile_block<'f, 'ctx>(block: &'f Block, func: &'f Function, codegen: &'f mu
                     .build_store(dest.into_pointer_value(), src)
                     .expect("Failed to store value");
             }
-            Instruction::Add(add_inst) => {
-                // 1) Get the pointer for the destination
-                let dest_ptr = match add_inst.get_dest() {
-                    Value::Instruction(dest_id) => {
-                        codegen.namend.get(dest_id).unwrap().into_pointer_value()
-                    }
-                    _ => unreachable!(),
-                };
+            Instruction::BinaryOp(bin_inst) => {
+                let op_ty = bin_inst.get_type();
+                let lhs = operand_as_value(bin_inst.get_lhs().clone(), &op_ty, codegen);
+                let rhs = operand_as_value(bin_inst.get_rhs().clone(), &op_ty, codegen);
+                let result = build_binary_op(bin_inst.get_op(), &op_ty, lhs, rhs, codegen);
 
-                // 2) LOAD LHS
-                let lhs_val = match add_inst.get_lhs().clone() {
-                    Value::Instruction(id) => {
-                        let ptr = codegen.namend.get(&id).unwrap().into_pointer_value();
-                        codegen
-                            .llvm_builder
-                            .build_load(to_llvm_type(add_inst.get_type(), codegen), ptr, "lhs_load")
-                            .unwrap()
-                            .into_int_value()
-                    }
-                    Value::ConstantInt(lit) => {
-                        // make sure you use i32_type() if your MIRType::Int32
-                        codegen.llvm_ctx.i32_type().const_int(lit as u64, false)
+                if let Value::Instruction(dest_id) = bin_inst.get_dest() {
+                    let dest_ptr = codegen.namend.get(dest_id).unwrap().into_pointer_value();
+                    codegen
+                        .llvm_builder
+                        .build_store(dest_ptr, result)
+                        .expect("store binary op result");
+                }
+
+                codegen.namend.insert(inst_id, result);
+            }
+
+            Instruction::Call(call_inst) => {
+                let callee = module
+                    .get_function(call_inst.get_callee())
+                    .expect("unknown callee FuncId");
+                let llvm_callee = codegen
+                    .llvm_mod
+                    .get_function(callee.get_name())
+                    .expect("callee was not declared before its caller");
+                let callee_params = callee.get_params();
+
+                // Struct args are passed byval, i.e. by the alloca pointer `Define` already
+                // produced; every other arg needs loading through `operand_as_value` the same
+                // way a binary op's operands do, or a scalar local's address leaks in as its value.
+                let mut args: Vec<BasicMetadataValueEnum> =
+                    Vec::with_capacity(call_inst.get_args().len());
+                for (arg, param_ty) in call_inst.get_args().iter().zip(callee_params.iter()) {
+                    let llvm_arg = match param_ty {
+                        MIRType::Struct(_) => to_llvm_value(arg.clone(), codegen),
+                        _ => operand_as_value(arg.clone(), param_ty, codegen),
+                    };
+                    args.push(llvm_arg.into());
+                }
+
+                let call_site = codegen
+                    .llvm_builder
+                    .build_call(llvm_callee, &args, "call")
+                    .expect("Failed to build call");
+
+                for (i, param_ty) in callee_params.iter().enumerate() {
+                    if let Some(byval_attr) = struct_byval_attribute(codegen.llvm_ctx, param_ty) {
+                        call_site.add_attribute(AttributeLoc::Param(i as u32), byval_attr);
                     }
-                    _ => unreachable!(),
-                };
+                }
+
+                if let Some(ret_value) = call_site.try_as_basic_value().left() {
+                    codegen.namend.insert(inst_id, ret_value);
+                }
+            }
+
+            Instruction::Gep(gep_inst) => {
+                let base_ptr =
+                    to_llvm_value(gep_inst.get_base().clone(), codegen).into_pointer_value();
+                let agg_ty = gep_inst.get_agg_ty();
+                let llvm_agg_ty = agg_ty.llvm_type(codegen.llvm_ctx);
+                let index = gep_inst.get_index();
 
-                // 3) LOAD or CONST RHS (similarly)
-                let rhs_val = match add_inst.get_rhs().clone() {
-                    Value::Instruction(id) => {
-                        let ptr = codegen.namend.get(&id).unwrap().into_pointer_value();
+                let elem_ptr = match agg_ty {
+                    MIRType::Struct(_) => {
+                        let struct_ty = match llvm_agg_ty {
+                            BasicTypeEnum::StructType(struct_ty) => struct_ty,
+                            _ => unreachable!("Struct MIRType must lower to an LLVM StructType"),
+                        };
                         codegen
                             .llvm_builder
-                            .build_load(to_llvm_type(add_inst.get_type(), codegen), ptr, "rhs_load")
-                            .unwrap()
-                            .into_int_value()
+                            .build_struct_gep(struct_ty, base_ptr, index as u32, "gep")
+                            .expect("Failed to build struct gep")
                     }
-                    Value::ConstantInt(lit) => {
-                        codegen.llvm_ctx.i32_type().const_int(lit as u64, false)
+                    MIRType::Array { .. } => {
+                        let i64_type = codegen.llvm_ctx.i64_type();
+                        let indices =
+                            [i64_type.const_int(0, false), i64_type.const_int(index, false)];
+                        unsafe {
+                            codegen
+                                .llvm_builder
+                                .build_in_bounds_gep(llvm_agg_ty, base_ptr, &indices, "gep")
+                                .expect("Failed to build array gep")
+                        }
                     }
-                    _ => unreachable!(),
+                    _ => panic!("Gep requires a Struct or Array MIRType"),
                 };
 
-                // 4) BUILD THE ACTUAL ADD INSTRUCTION
-                let sum = codegen
-                    .llvm_builder
-                    .build_int_add(lhs_val, rhs_val, "add")
-                    .unwrap();
+                codegen
+                    .namend
+                    .insert(inst_id, elem_ptr.as_basic_value_enum());
+            }
 
-                // 5) STORE THE RESULT BACK
+            Instruction::Br(br_inst) => {
+                let target = blocks
+                    .get(&br_inst.get_target())
+                    .expect("branch target not found in block map");
                 codegen
                     .llvm_builder
-                    .build_store(dest_ptr, sum)
-                    .expect("store sum");
+                    .build_unconditional_branch(*target)
+                    .expect("Failed to build br");
+            }
 
-                // 6) And—very important—remember to put *this* result into your map
-                codegen.namend.insert(inst_id, sum.as_basic_value_enum());
+            Instruction::CondBr(cond_br_inst) => {
+                let cond = to_llvm_value(cond_br_inst.get_cond().clone(), codegen).into_int_value();
+                let then_bb = blocks
+                    .get(&cond_br_inst.get_then_bb())
+                    .expect("then target not found in block map");
+                let else_bb = blocks
+                    .get(&cond_br_inst.get_else_bb())
+                    .expect("else target not found in block map");
+                codegen
+                    .llvm_builder
+                    .build_conditional_branch(cond, *then_bb, *else_bb)
+                    .expect("Failed to build condbr");
             }
 
             Instruction::Ret(ret_inst) => {
@@ -151,63 +504,179 @@ fn compile_block<'f, 'ctx>(block: &'f Block, func: &'f Function, codegen: &'f mu
     }
 }
 
-fn compile_function<'f, 'ctx>(function: &'f Function, codegen: &'f mut Codegen<'ctx>) {
-    let ret_type = to_llvm_type(function.get_ret_type(), codegen);
-    let fn_type = ret_type.fn_type(&[], false);
+/// Builds the `byval` type attribute for a struct parameter, or `None` for any other type.
+///
+/// `byval` must be attached both to the callee's own parameter list and to every call site that
+/// passes it, so this is shared between `declare_function` and the `Call` lowering.
+fn struct_byval_attribute<'ctx>(llvm_ctx: &'ctx Context, param_ty: &MIRType) -> Option<Attribute> {
+    match param_ty {
+        MIRType::Struct(_) => {
+            let struct_llvm_ty = match param_ty.llvm_type(llvm_ctx) {
+                BasicTypeEnum::StructType(struct_ty) => struct_ty,
+                _ => unreachable!("MIRType::Struct must lower to an LLVM StructType"),
+            };
+            let byval_kind = Attribute::get_named_enum_kind_id("byval");
+            Some(llvm_ctx.create_type_attribute(byval_kind, struct_llvm_ty.as_any_type_enum()))
+        }
+        _ => None,
+    }
+}
+
+/// Declares `function`'s signature in `llvm_mod` without lowering its body, so that calls
+/// between MIR functions resolve regardless of definition order.
+///
+/// Struct parameters are spilled to the stack by the caller per the platform ABI: they are
+/// passed as a pointer marked `byval`, rather than by value.
+fn declare_function<'ctx>(function: &Function, codegen: &mut Codegen<'ctx>) {
+    let param_types: Vec<BasicMetadataTypeEnum> = function
+        .get_params()
+        .iter()
+        .map(|param_ty| match param_ty {
+            MIRType::Struct(_) => codegen.llvm_ctx.ptr_type(AddressSpace::default()).into(),
+            _ => param_ty.llvm_type(codegen.llvm_ctx).into(),
+        })
+        .collect();
+
+    let ret_type = function.get_ret_type().llvm_type(codegen.llvm_ctx);
+    let fn_type = ret_type.fn_type(&param_types, false);
     let llvm_func = codegen
         .llvm_mod
         .add_function(function.get_name(), fn_type, None);
 
-    for block in function.get_blocks() {
+    for (i, param_ty) in function.get_params().iter().enumerate() {
+        if let Some(byval_attr) = struct_byval_attribute(codegen.llvm_ctx, param_ty) {
+            llvm_func.add_attribute(AttributeLoc::Param(i as u32), byval_attr);
+        }
+    }
+}
+
+/// Creates a `DISubprogram` for `function` and attaches it to its already-declared LLVM function,
+/// so the instructions lowered under it can carry a debug location back to this scope.
+fn create_subprogram<'ctx>(function: &Function, codegen: &Codegen<'ctx>) -> DISubprogram<'ctx> {
+    let debug_info = codegen
+        .debug_info
+        .as_ref()
+        .expect("debug info builder not initialized");
+    let di_builder = debug_info.builder();
+    let file = debug_info.compile_unit().get_file();
+
+    let subroutine_type = di_builder.create_subroutine_type(file, None, &[], DIFlags::PUBLIC);
+    let subprogram = di_builder.create_function(
+        debug_info.compile_unit().as_debug_info_scope(),
+        function.get_name(),
+        None,
+        file,
+        1,
+        subroutine_type,
+        false,
+        true,
+        1,
+        DIFlags::PUBLIC,
+        false,
+    );
+
+    let llvm_func = codegen
+        .llvm_mod
+        .get_function(function.get_name())
+        .expect("function was not declared before create_subprogram");
+    llvm_func.set_subprogram(subprogram);
+    subprogram
+}
+
+fn compile_function<'f, 'ctx>(
+    function: &'f Function,
+    module: &'f Module,
+    codegen: &'f mut Codegen<'ctx>,
+) -> Result<(), CfgError> {
+    let llvm_func = codegen
+        .llvm_mod
+        .get_function(function.get_name())
+        .expect("function was not declared before compile_function");
+
+    codegen.param_values.clear();
+    for (i, _) in function.get_params().iter().enumerate() {
+        let param = llvm_func
+            .get_nth_param(i as u32)
+            .expect("declared parameter missing from llvm function");
+        codegen.param_values.insert(i, param);
+    }
+
+    let subprogram = codegen
+        .debug_enabled()
+        .then(|| create_subprogram(function, codegen));
+
+    // Pass 1: append every basic block up front so forward branches resolve.
+    let mut blocks = HashMap::new();
+    for (i, block) in function.get_blocks().iter().enumerate() {
         let bb = codegen
             .llvm_ctx
             .append_basic_block(llvm_func, block.get_name());
+        blocks.insert(BlockId(i), bb);
+    }
+
+    // Pass 2: lower each block's body against the now-complete block map.
+    for (i, block) in function.get_blocks().iter().enumerate() {
+        let bb = blocks[&BlockId(i)];
         codegen.llvm_builder.position_at_end(bb);
-        compile_block(block, function, codegen);
+        compile_block(block, function, module, codegen, &blocks, subprogram);
     }
+
+    Ok(())
 }
 
-fn compile<'ctx>(module: &Module, codegen: &mut Codegen<'ctx>) {
+fn compile<'ctx>(module: &mut Module, codegen: &mut Codegen<'ctx>) -> Result<(), CfgError> {
     for func in module.get_functions() {
-        compile_function(func, codegen);
+        declare_function(func, codegen);
+    }
+    for func in module.get_functions_mut() {
+        verify_cfg(func)?;
     }
+    for func in module.get_functions() {
+        compile_function(func, module, codegen)?;
+    }
+    if let Some(debug_info) = &codegen.debug_info {
+        debug_info.finalize();
+    }
+    Ok(())
 }
 
 fn main() {
+    Target::initialize_native(&InitializationConfig::default())
+        .expect("Failed to initialize native target");
+
     let llvm_ctx = Context::create();
-    let mut codeg = Codegen {
-        llvm_ctx: &llvm_ctx,
-        llvm_mod: llvm_ctx.create_module("main"),
-        llvm_builder: llvm_ctx.create_builder(),
-        namend: HashMap::new(),
-    };
+    let mut codeg = Codegen::new(&llvm_ctx, "main", None);
 
     let mut module = Module::new("main");
-    let main_handle = module.add_function(Function::new("main", MIRType::Int32));
+    let main_handle = module.add_function(Function::new("main", MIRType::i32(), Vec::new()));
 
     let entry_block = Block::new("entry", InstId(0));
 
     let function = module.get_function_mut(main_handle).unwrap();
     let entry_handle = function.add_block(entry_block);
 
-    let define_inst = Instruction::Define(DefineInst::new(MIRType::Int32, Value::ConstantInt(69)));
+    let define_inst = Instruction::Define(DefineInst::new(
+        MIRType::i32(),
+        Value::ConstantInt(69, MIRType::i32()),
+    ));
     let define_inst_id = function.add_instruction(define_inst);
     let block = function.get_block_mut(entry_handle).unwrap();
     block.adjust_range(define_inst_id);
 
     let new_define_inst = Instruction::Define(DefineInst::new(
-        MIRType::Int32,
+        MIRType::i32(),
         Value::Instruction(define_inst_id),
     ));
     let new_define_inst_id = function.add_instruction(new_define_inst);
     let block = function.get_block_mut(entry_handle).unwrap();
     block.adjust_range(new_define_inst_id);
 
-    let add_inst = Instruction::Add(AddInst::new(
+    let add_inst = Instruction::BinaryOp(BinaryOpInst::new(
         Value::Instruction(define_inst_id),
+        BinOp::Add,
         Value::Instruction(new_define_inst_id),
-        Value::ConstantInt(2),
-        MIRType::Int32,
+        Value::ConstantInt(2, MIRType::i32()),
+        MIRType::i32(),
     ));
 
     let add_inst_id = function.add_instruction(add_inst);
@@ -221,9 +690,13 @@ fn main() {
 
     println!("{:#?}", module);
 
-    compile(&module, &mut codeg); //here
+    compile(&mut module, &mut codeg).expect("module has an invalid control-flow graph");
     codeg.llvm_mod.verify().unwrap();
+    codeg.optimize().expect("optimization pipeline failed");
     codeg
         .llvm_mod
         .print_to_stderr();
+
+    let result = codeg.jit_run("main").expect("JIT execution failed");
+    println!("main() = {result}");
 }
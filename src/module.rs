@@ -28,6 +28,10 @@ impl<'ctx> Module<'ctx> {
         &self.funcs
     }
 
+    pub fn get_functions_mut(&mut self) -> &mut [Function<'ctx>] {
+        &mut self.funcs
+    }
+
     pub fn get_function(&self, id: FuncId) -> Option<&Function<'ctx>> {
         self.funcs.get(id.0)
     }
@@ -0,0 +1,12 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span<'ctx> {
+    pub file: &'ctx str,
+    pub line: u32,
+    pub col: u32,
+}
+
+impl<'ctx> Span<'ctx> {
+    pub fn new(file: &'ctx str, line: u32, col: u32) -> Self {
+        Span { file, line, col }
+    }
+}
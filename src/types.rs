@@ -0,0 +1,81 @@
+use inkwell::AddressSpace;
+use inkwell::context::Context;
+use inkwell::targets::TargetData;
+use inkwell::types::{BasicType, BasicTypeEnum};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FloatKind {
+    F32,
+    F64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MIRType {
+    Int { bits: u32, signed: bool },
+    Float(FloatKind),
+    Bool,
+    Ptr(Box<MIRType>),
+    Array { elem: Box<MIRType>, len: u64 },
+    Struct(Vec<MIRType>),
+}
+
+impl MIRType {
+    pub fn i32() -> Self {
+        MIRType::Int {
+            bits: 32,
+            signed: true,
+        }
+    }
+
+    pub fn i64() -> Self {
+        MIRType::Int {
+            bits: 64,
+            signed: true,
+        }
+    }
+
+    pub fn ptr(pointee: MIRType) -> Self {
+        MIRType::Ptr(Box::new(pointee))
+    }
+
+    pub fn array(elem: MIRType, len: u64) -> Self {
+        MIRType::Array {
+            elem: Box::new(elem),
+            len,
+        }
+    }
+
+    pub fn is_signed(&self) -> bool {
+        matches!(self, MIRType::Int { signed: true, .. })
+    }
+
+    /// Ask the type for its LLVM representation rather than hardcoding a width at each call site.
+    pub fn llvm_type<'ctx>(&self, ctx: &'ctx Context) -> BasicTypeEnum<'ctx> {
+        match self {
+            MIRType::Int { bits, .. } => ctx.custom_width_int_type(*bits).into(),
+            MIRType::Bool => ctx.bool_type().into(),
+            MIRType::Float(FloatKind::F32) => ctx.f32_type().into(),
+            MIRType::Float(FloatKind::F64) => ctx.f64_type().into(),
+            MIRType::Ptr(_) => ctx.ptr_type(AddressSpace::default()).into(),
+            MIRType::Array { elem, len } => elem.llvm_type(ctx).array_type(*len as u32).into(),
+            MIRType::Struct(fields) => {
+                let field_types: Vec<BasicTypeEnum> =
+                    fields.iter().map(|field| field.llvm_type(ctx)).collect();
+                ctx.struct_type(&field_types, false).into()
+            }
+        }
+    }
+
+    /// Naive (unpadded) byte size, matching the alloca-per-value model the rest of codegen uses.
+    pub fn size_of(&self, target_data: &TargetData) -> u64 {
+        match self {
+            MIRType::Int { bits, .. } => (*bits as u64).div_ceil(8),
+            MIRType::Bool => 1,
+            MIRType::Float(FloatKind::F32) => 4,
+            MIRType::Float(FloatKind::F64) => 8,
+            MIRType::Ptr(_) => target_data.get_pointer_byte_size(None) as u64,
+            MIRType::Array { elem, len } => elem.size_of(target_data) * len,
+            MIRType::Struct(fields) => fields.iter().map(|field| field.size_of(target_data)).sum(),
+        }
+    }
+}
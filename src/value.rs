@@ -1,8 +1,9 @@
-use crate::instruction::InstId;
+use crate::{instruction::InstId, types::MIRType};
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub enum Value {
     Instruction(InstId),
-    ConstantInt(i64),
-    ConstantFloat(f64),
+    Param(usize),
+    ConstantInt(i64, MIRType),
+    ConstantFloat(f64, MIRType),
 }
\ No newline at end of file